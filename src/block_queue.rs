@@ -0,0 +1,284 @@
+use crate::types::{Block, Error};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// A snapshot of how many blocks are sitting in each stage of a `BlockQueue`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueInfo {
+    pub unverified: usize,
+    pub verifying: usize,
+    pub verified: usize,
+}
+
+/// A block awaiting verification, paired with the sequence number it was pushed under.
+struct Pending {
+    sequence: u64,
+    block: Block,
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.sequence == other.sequence
+    }
+}
+impl Eq for Pending {}
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.sequence.cmp(&other.sequence)
+    }
+}
+
+/// The outcome of verifying a block, paired with the sequence number it was pushed
+/// under, so both passing and failing blocks keep their place in the arrival order
+/// instead of a rejected block silently vanishing and wedging every block behind it.
+struct Verified {
+    sequence: u64,
+    result: Result<Block, Error>,
+}
+
+impl PartialEq for Verified {
+    fn eq(&self, other: &Self) -> bool {
+        self.sequence == other.sequence
+    }
+}
+impl Eq for Verified {}
+impl PartialOrd for Verified {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Verified {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.sequence.cmp(&other.sequence)
+    }
+}
+
+struct Shared {
+    unverified: VecDeque<Pending>,
+    verifying: usize,
+    verified: BinaryHeap<Reverse<Verified>>,
+    next_in: u64,
+    next_out: u64,
+    closed: bool,
+}
+
+impl Shared {
+    fn info(&self) -> QueueInfo {
+        QueueInfo {
+            unverified: self.unverified.len(),
+            verifying: self.verifying,
+            verified: self.verified.len(),
+        }
+    }
+}
+
+/// Verifies incoming blocks across a pool of worker threads so a caller doing bulk sync
+/// doesn't pay hash/signature/PoW verification cost on its own thread, while still
+/// importing blocks into the chain in the order they arrived.
+///
+/// Modeled on OpenEthereum's block queue: `push` drops a block into an unverified queue,
+/// worker threads pull from it and move its outcome into a verified queue, and
+/// `pop_verified` blocks a consumer until the next result in sequence is ready. A
+/// rejected block surfaces as an `Err` at its place in the sequence rather than being
+/// dropped, so one bad block can't wedge every valid block behind it.
+///
+/// Verification here covers the hash and proof-of-work checks `Blockchain::append_block`
+/// performs up front (`Block::verify`, `Block::meets_difficulty`), which don't depend on
+/// chain state. Signature and account-state validation still happen sequentially during
+/// import, since whether a block is the genesis block (and so exempt from signature
+/// checks) isn't known until it reaches the front of the chain.
+pub struct BlockQueue {
+    shared: Arc<Mutex<Shared>>,
+    has_work: Arc<Condvar>,
+    has_verified: Arc<Condvar>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl BlockQueue {
+    /// Spawns `worker_count` verification threads, or one thread if `worker_count` is 0.
+    /// `worker_count` is typically `num_cpus::get()`.
+    pub fn new(worker_count: usize) -> Self {
+        let shared = Arc::new(Mutex::new(Shared {
+            unverified: VecDeque::new(),
+            verifying: 0,
+            verified: BinaryHeap::new(),
+            next_in: 0,
+            next_out: 0,
+            closed: false,
+        }));
+        let has_work = Arc::new(Condvar::new());
+        let has_verified = Arc::new(Condvar::new());
+
+        let workers = (0..worker_count.max(1))
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                let has_work = Arc::clone(&has_work);
+                let has_verified = Arc::clone(&has_verified);
+                thread::spawn(move || Self::verify_loop(shared, has_work, has_verified))
+            })
+            .collect();
+
+        BlockQueue {
+            shared,
+            has_work,
+            has_verified,
+            workers,
+        }
+    }
+
+    fn verify_loop(
+        shared: Arc<Mutex<Shared>>,
+        has_work: Arc<Condvar>,
+        has_verified: Arc<Condvar>,
+    ) {
+        loop {
+            let pending = {
+                let mut state = shared.lock().unwrap();
+                loop {
+                    if let Some(pending) = state.unverified.pop_front() {
+                        state.verifying += 1;
+                        break pending;
+                    }
+                    if state.closed {
+                        return;
+                    }
+                    state = has_work.wait(state).unwrap();
+                }
+            };
+
+            let result = if pending.block.verify() && pending.block.meets_difficulty() {
+                Ok(pending.block)
+            } else {
+                Err("Block has invalid hash or doesn't satisfy its stated difficulty".to_string())
+            };
+
+            let mut state = shared.lock().unwrap();
+            state.verifying -= 1;
+            state.verified.push(Reverse(Verified {
+                sequence: pending.sequence,
+                result,
+            }));
+            has_verified.notify_all();
+        }
+    }
+
+    /// Queues `block` for verification, preserving its place in the import order.
+    pub fn push(&self, block: Block) {
+        let mut state = self.shared.lock().unwrap();
+        let sequence = state.next_in;
+        state.next_in += 1;
+        state.unverified.push_back(Pending { sequence, block });
+        self.has_work.notify_one();
+    }
+
+    /// A snapshot of how many blocks are unverified, currently being verified, and
+    /// verified but not yet drained.
+    pub fn info(&self) -> QueueInfo {
+        self.shared.lock().unwrap().info()
+    }
+
+    /// Blocks until the next block in arrival order has been verified, then returns its
+    /// outcome — `Ok` if it passed, `Err` if it was rejected.
+    pub fn pop_verified(&self) -> Result<Block, Error> {
+        let mut state = self.shared.lock().unwrap();
+        loop {
+            if let Some(Reverse(verified)) = state.verified.peek() {
+                if verified.sequence == state.next_out {
+                    let Reverse(verified) = state.verified.pop().unwrap();
+                    state.next_out += 1;
+                    return verified.result;
+                }
+            }
+            state = self.has_verified.wait(state).unwrap();
+        }
+    }
+
+    /// Non-blocking version of `pop_verified`: returns `None` if the next block in
+    /// arrival order hasn't been verified yet.
+    pub fn try_pop_verified(&self) -> Option<Result<Block, Error>> {
+        let mut state = self.shared.lock().unwrap();
+        match state.verified.peek() {
+            Some(Reverse(verified)) if verified.sequence == state.next_out => {
+                let Reverse(verified) = state.verified.pop().unwrap();
+                state.next_out += 1;
+                Some(verified.result)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Drop for BlockQueue {
+    fn drop(&mut self) {
+        self.shared.lock().unwrap().closed = true;
+        self.has_work.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verifies_and_drains_in_order() {
+        let queue = BlockQueue::new(4);
+        let mut block_a = Block::new(None);
+        block_a.set_nonce(1);
+        let mut block_b = Block::new(block_a.hash.clone());
+        block_b.set_nonce(2);
+
+        queue.push(block_a.clone());
+        queue.push(block_b.clone());
+
+        assert_eq!(queue.pop_verified().unwrap().hash, block_a.hash);
+        assert_eq!(queue.pop_verified().unwrap().hash, block_b.hash);
+    }
+
+    #[test]
+    fn test_rejected_block_surfaces_as_err_without_wedging_later_blocks() {
+        let queue = BlockQueue::new(2);
+        let mut bad = Block::new(None);
+        bad.hash = Some("deadbeef".to_string());
+        let good = Block::new(None);
+
+        queue.push(bad);
+        queue.push(good.clone());
+
+        assert!(queue.pop_verified().is_err());
+        assert_eq!(queue.pop_verified().unwrap().hash, good.hash);
+    }
+
+    #[test]
+    fn test_try_pop_verified_is_non_blocking() {
+        let queue = BlockQueue::new(1);
+        assert!(queue.try_pop_verified().is_none());
+
+        let block = Block::new(None);
+        queue.push(block.clone());
+        while queue.info().verified == 0 {
+            thread::yield_now();
+        }
+        assert_eq!(
+            queue.try_pop_verified().map(|r| r.unwrap().hash),
+            Some(block.hash)
+        );
+    }
+
+    #[test]
+    fn test_info_reports_queue_depth() {
+        let queue = BlockQueue::new(0);
+        queue.push(Block::new(None));
+        let info = queue.info();
+        assert_eq!(info.unverified + info.verifying + info.verified, 1);
+    }
+}
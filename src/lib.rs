@@ -0,0 +1,6 @@
+pub mod block_queue;
+pub mod storage;
+pub mod traits;
+pub mod types;
+#[cfg(test)]
+pub(crate) mod utils;
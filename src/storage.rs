@@ -0,0 +1,179 @@
+use crate::traits::Hashable;
+use crate::types::{Block, Blockchain, Error};
+use rusqlite::{params, Connection, OptionalExtension};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS blocks (
+    height INTEGER PRIMARY KEY,
+    hash TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS chain_tip (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    height INTEGER NOT NULL,
+    hash TEXT NOT NULL
+);
+";
+
+/// Persists a `Blockchain`'s blocks (transactions included) to a SQLite database.
+///
+/// Blocks are stored as JSON keyed by height. There is no separate account snapshot, so
+/// rebuilding account state on `open()` still replays every stored block through
+/// `Blockchain::append_block` and is O(n) in the number of blocks stored. The `chain_tip`
+/// table is a separate, single-row index that tracks the current height/hash so the tip
+/// can be looked up via `height()` in O(1), without scanning or replaying `blocks`.
+pub struct Storage {
+    conn: Connection,
+}
+
+impl Storage {
+    /// Opens (creating if absent) the SQLite database at `path`, replaying every stored
+    /// block through `Blockchain::append_block` to rebuild in-memory state.
+    pub fn open(path: &str) -> Result<(Self, Blockchain), Error> {
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+
+        let mut blockchain = Blockchain::new();
+        let mut stmt = conn
+            .prepare("SELECT data FROM blocks ORDER BY height ASC")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let data = row.map_err(|e| e.to_string())?;
+            let block: Block = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+            blockchain
+                .append_block(block)
+                .map_err(|e| format!("Failed to replay stored block: {}", e))?;
+        }
+        drop(stmt);
+
+        let storage = Storage { conn };
+        if let Some(tip_height) = storage.height()? {
+            if tip_height != blockchain.len() {
+                return Err(format!(
+                    "chain_tip index reports height {} but replaying `blocks` produced {}; the database is corrupt",
+                    tip_height,
+                    blockchain.len()
+                ));
+            }
+        }
+
+        Ok((storage, blockchain))
+    }
+
+    /// The chain's current height per the `chain_tip` index, or `None` if the chain is
+    /// still empty. This is a single indexed row lookup (O(1)), unlike rebuilding
+    /// account state, which requires the full O(n) replay in `open()`.
+    pub fn height(&self) -> Result<Option<usize>, Error> {
+        self.conn
+            .query_row("SELECT height FROM chain_tip WHERE id = 0", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()
+            .map_err(|e| e.to_string())
+            .map(|height| height.map(|h| h as usize))
+    }
+
+    /// Writes `block` (at chain `height`) and updates the `chain_tip` index, all inside
+    /// one SQLite transaction, so a crash mid-append cannot leave a partial row behind
+    /// or a `blocks` row without a matching tip update.
+    pub fn append_block(&mut self, height: usize, block: &Block) -> Result<(), Error> {
+        let data = serde_json::to_string(block).map_err(|e| e.to_string())?;
+        let hash = block.hash();
+
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO blocks (height, hash, data) VALUES (?1, ?2, ?3)",
+            params![height as i64, hash, data],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO chain_tip (id, height, hash) VALUES (0, ?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET height = excluded.height, hash = excluded.hash",
+            params![height as i64, hash],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::WorldState;
+    use crate::types::TransactionData;
+    use crate::types::Transaction;
+    use std::fs;
+
+    fn temp_db_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "my_blockchain_storage_test_{}_{}.sqlite",
+            std::process::id(),
+            name
+        ))
+    }
+
+    #[test]
+    fn test_open_creates_fresh_empty_chain() {
+        let path = temp_db_path("fresh");
+        let _ = fs::remove_file(&path);
+
+        let (_, blockchain) = Storage::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(blockchain.len(), 0);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_append_block_round_trips_through_reopen() {
+        let path = temp_db_path("roundtrip");
+        let _ = fs::remove_file(&path);
+
+        let mut block = Block::new(None);
+        block.set_nonce(1);
+        block.add_transaction(Transaction::new(
+            TransactionData::CreateAccount("satoshi".to_string()),
+            None,
+        ));
+        block.add_transaction(Transaction::new(
+            TransactionData::MintInitialSupply {
+                to: "satoshi".to_string(),
+                amount: 100,
+            },
+            None,
+        ));
+
+        {
+            let (mut storage, _) = Storage::open(path.to_str().unwrap()).unwrap();
+            storage.append_block(1, &block).unwrap();
+        }
+
+        let (storage, blockchain) = Storage::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(blockchain.len(), 1);
+        assert_eq!(
+            blockchain
+                .get_account_by_id("satoshi".to_string())
+                .unwrap()
+                .balance,
+            100
+        );
+        assert_eq!(storage.height().unwrap(), Some(1));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_height_is_none_for_a_fresh_chain() {
+        let path = temp_db_path("height_fresh");
+        let _ = fs::remove_file(&path);
+
+        let (storage, _) = Storage::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(storage.height().unwrap(), None);
+
+        let _ = fs::remove_file(&path);
+    }
+}
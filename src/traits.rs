@@ -1,5 +1,13 @@
-use crate::types::Hash;
+use crate::types::{Account, AccountId, AccountType, Error, Hash};
 
 pub trait Hashable {
-    fn hash() -> Hash;
-}
\ No newline at end of file
+    fn hash(&self) -> Hash;
+}
+
+/// The minimal account-ledger interface a transaction needs to execute against, so
+/// `Transaction::execute` isn't coupled directly to `Blockchain`.
+pub trait WorldState {
+    fn create_account(&mut self, account_id: AccountId, account_type: AccountType) -> Result<(), Error>;
+    fn get_account_by_id(&self, account_id: AccountId) -> Option<&Account>;
+    fn get_account_by_id_mut(&mut self, account_id: AccountId) -> Option<&mut Account>;
+}
@@ -1,11 +1,31 @@
 use crate::types::Balance;
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AccountType {
     User,
     Contract,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
     account_hide: AccountType,
-    balance: Balance,
-}
\ No newline at end of file
+    pub(crate) balance: Balance,
+    /// Next nonce this account is expected to use, to prevent replaying a signed
+    /// transaction more than once.
+    pub(crate) nonce: u128,
+}
+
+impl Account {
+    pub fn new(account_type: AccountType) -> Self {
+        Account {
+            account_hide: account_type,
+            balance: 0,
+            nonce: 0,
+        }
+    }
+
+    pub fn nonce(&self) -> u128 {
+        self.nonce
+    }
+}
@@ -0,0 +1,155 @@
+use crate::types::{Account, AccountId, AccountType, Error};
+use std::collections::HashMap;
+
+/// Per-checkpoint journal: for every account touched since the checkpoint was opened,
+/// the account's value right before the first touch, or `None` if it didn't exist yet.
+type Journal = HashMap<AccountId, Option<Account>>;
+
+/// The account set backing a `Blockchain`, with a stack of checkpoints that let
+/// `append_block` roll back a failed block or transaction without cloning the whole map.
+#[derive(Default, Debug)]
+pub struct AccountState {
+    accounts: HashMap<AccountId, Account>,
+    checkpoints: Vec<Journal>,
+}
+
+impl AccountState {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Opens a new checkpoint. Changes made after this call can be undone in one step
+    /// with `revert_to_checkpoint`, without touching any checkpoint opened before it.
+    pub fn checkpoint(&mut self) {
+        self.checkpoints.push(Journal::new());
+    }
+
+    /// Undoes every change recorded since the most recently opened checkpoint.
+    pub fn revert_to_checkpoint(&mut self) {
+        let journal = self
+            .checkpoints
+            .pop()
+            .expect("revert_to_checkpoint called without an open checkpoint");
+        for (account_id, pre_image) in journal {
+            match pre_image {
+                Some(account) => {
+                    self.accounts.insert(account_id, account);
+                }
+                None => {
+                    self.accounts.remove(&account_id);
+                }
+            }
+        }
+    }
+
+    /// Accepts the changes made since the most recently opened checkpoint, folding
+    /// their pre-images into the checkpoint below (if any) so it can still be reverted.
+    pub fn discard_checkpoint(&mut self) {
+        let journal = self
+            .checkpoints
+            .pop()
+            .expect("discard_checkpoint called without an open checkpoint");
+        if let Some(parent) = self.checkpoints.last_mut() {
+            for (account_id, pre_image) in journal {
+                parent.entry(account_id).or_insert(pre_image);
+            }
+        }
+    }
+
+    fn record_pre_image(&mut self, account_id: &AccountId) {
+        if self.checkpoints.is_empty() {
+            return;
+        }
+        let pre_image = self.accounts.get(account_id).cloned();
+        let journal = self.checkpoints.last_mut().unwrap();
+        journal.entry(account_id.clone()).or_insert(pre_image);
+    }
+
+    pub fn create_account(
+        &mut self,
+        account_id: AccountId,
+        account_type: AccountType,
+    ) -> Result<(), Error> {
+        if self.accounts.contains_key(&account_id) {
+            return Err(format!("Account id already exist: {}", account_id));
+        }
+        self.record_pre_image(&account_id);
+        self.accounts.insert(account_id, Account::new(account_type));
+        Ok(())
+    }
+
+    pub fn get_account_by_id(&self, account_id: &AccountId) -> Option<&Account> {
+        self.accounts.get(account_id)
+    }
+
+    pub fn get_account_by_id_mut(&mut self, account_id: &AccountId) -> Option<&mut Account> {
+        if self.accounts.contains_key(account_id) {
+            self.record_pre_image(account_id);
+        }
+        self.accounts.get_mut(account_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revert_restores_previous_values() {
+        let mut state = AccountState::new();
+        state
+            .create_account("alice".to_string(), AccountType::User)
+            .unwrap();
+        state.get_account_by_id_mut(&"alice".to_string()).unwrap().balance = 100;
+
+        state.checkpoint();
+        state.get_account_by_id_mut(&"alice".to_string()).unwrap().balance = 50;
+        state
+            .create_account("bob".to_string(), AccountType::User)
+            .unwrap();
+        state.revert_to_checkpoint();
+
+        assert_eq!(state.get_account_by_id(&"alice".to_string()).unwrap().balance, 100);
+        assert!(state.get_account_by_id(&"bob".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_discard_merges_into_parent_checkpoint() {
+        let mut state = AccountState::new();
+        state
+            .create_account("alice".to_string(), AccountType::User)
+            .unwrap();
+        state.get_account_by_id_mut(&"alice".to_string()).unwrap().balance = 100;
+
+        state.checkpoint();
+        state.get_account_by_id_mut(&"alice".to_string()).unwrap().balance = 50;
+        state.checkpoint();
+        state.get_account_by_id_mut(&"alice".to_string()).unwrap().balance = 25;
+        state.discard_checkpoint();
+        state.revert_to_checkpoint();
+
+        assert_eq!(state.get_account_by_id(&"alice".to_string()).unwrap().balance, 100);
+    }
+
+    #[test]
+    fn test_nested_checkpoints_roll_back_independently() {
+        let mut state = AccountState::new();
+        state
+            .create_account("alice".to_string(), AccountType::User)
+            .unwrap();
+
+        state.checkpoint();
+        state
+            .create_account("bob".to_string(), AccountType::User)
+            .unwrap();
+
+        state.checkpoint();
+        state
+            .create_account("carol".to_string(), AccountType::User)
+            .unwrap();
+        state.revert_to_checkpoint();
+
+        assert!(state.get_account_by_id(&"bob".to_string()).is_some());
+        assert!(state.get_account_by_id(&"carol".to_string()).is_none());
+    }
+}
@@ -1,14 +1,20 @@
 use blake2::{Blake2s, Digest};
 use blake2::digest::FixedOutput;
 use crate::traits::Hashable;
-use crate::types::{Hash, Transaction};
+use crate::types::{Hash, Timestamp, Transaction};
+use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Clone)]
+/// How often we'd like blocks to be mined, in milliseconds.
+pub const TARGET_BLOCK_INTERVAL_MS: Timestamp = 10_000;
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
     nonce: u128,
     pub(crate) hash: Option<Hash>,
-    prev_hash: Option<Hash>,
-    transactions: Vec<Transaction>
+    pub(crate) prev_hash: Option<Hash>,
+    pub(crate) transactions: Vec<Transaction>,
+    difficulty: u32,
+    timestamp: Timestamp,
 }
 
 impl Block {
@@ -25,16 +31,50 @@ impl Block {
         matches!(&self.hash, Some(hash) if hash == &self.hash())
     }
 
+    /// Whether the block's hash satisfies its own stated `difficulty`.
+    pub fn meets_difficulty(&self) -> bool {
+        match &self.hash {
+            Some(hash) => leading_zero_nibbles(hash) >= self.difficulty,
+            None => false,
+        }
+    }
+
+    pub fn difficulty(&self) -> u32 {
+        self.difficulty
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
     pub fn set_nonce(&mut self, nonce: u128) {
         self.nonce = nonce;
         self.update_hash();
     }
 
+    pub fn set_timestamp(&mut self, timestamp: Timestamp) {
+        self.timestamp = timestamp;
+        self.update_hash();
+    }
+
     pub fn add_transaction(&mut self, transaction: Transaction) {
         self.transactions.push(transaction);
         self.update_hash();
     }
 
+    /// Mines the block at `difficulty`, incrementing `nonce` until the hash has at least
+    /// `difficulty` leading zero hex nibbles.
+    pub fn mine(&mut self, difficulty: u32) {
+        self.difficulty = difficulty;
+        loop {
+            self.update_hash();
+            if self.meets_difficulty() {
+                break;
+            }
+            self.nonce += 1;
+        }
+    }
+
     fn update_hash(&mut self) {
         self.hash = Some(self.hash());
     }
@@ -43,7 +83,13 @@ impl Block {
 impl Hashable for Block {
     fn hash(&self) -> Hash {
         let mut hasher = Blake2s::new();
-        hasher.update(format!("{:?}", (self.prev_hash.clone(), self.nonce)).as_bytes());
+        hasher.update(
+            format!(
+                "{:?}",
+                (self.prev_hash.clone(), self.nonce, self.difficulty)
+            )
+            .as_bytes(),
+        );
         for tx in self.transactions.iter() {
             hasher.update(tx.hash());
         }
@@ -51,6 +97,29 @@ impl Hashable for Block {
     }
 }
 
+fn leading_zero_nibbles(hash: &str) -> u32 {
+    hash.chars().take_while(|&c| c == '0').count() as u32
+}
+
+/// Adjusts `prev_difficulty` based on how long the last block actually took to mine
+/// relative to `TARGET_BLOCK_INTERVAL_MS`: raises it by one when the block came in faster
+/// than half the target interval, lowers it by one (not below zero) when it took more than
+/// twice the target, and leaves it unchanged otherwise. Each step is one hex nibble, so it
+/// changes the expected mining work by a factor of 16.
+pub fn retarget_difficulty(prev_difficulty: u32, actual_interval_ms: Timestamp) -> u32 {
+    if actual_interval_ms == 0 {
+        return prev_difficulty.saturating_add(1);
+    }
+
+    if actual_interval_ms < TARGET_BLOCK_INTERVAL_MS / 2 {
+        prev_difficulty.saturating_add(1)
+    } else if actual_interval_ms > TARGET_BLOCK_INTERVAL_MS * 2 {
+        prev_difficulty.saturating_sub(1)
+    } else {
+        prev_difficulty
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::types::TransactionData;
@@ -59,7 +128,7 @@ mod tests {
     #[test]
     fn test_creation() {
         let mut block = Block::new(None);
-        let mut tx = Transaction::new(TransactionData::CreateAccount("alice".to_string()), None);
+        let tx = Transaction::new(TransactionData::CreateAccount("alice".to_string()), None);
         block.set_nonce(1);
         block.add_transaction(tx);
         dbg!(block);
@@ -71,10 +140,26 @@ mod tests {
         block.set_nonce(1);
         let hash1 = block.hash();
 
-        let mut tx = Transaction::new(TransactionData::CreateAccount("alice".to_string()), None);
+        let tx = Transaction::new(TransactionData::CreateAccount("alice".to_string()), None);
         block.transactions.push(tx);
         let hash2 = block.hash();
 
         assert_ne!(hash1, hash2);
     }
+
+    #[test]
+    fn test_mine_satisfies_difficulty() {
+        let mut block = Block::new(None);
+        block.mine(2);
+        assert!(block.meets_difficulty());
+        assert_eq!(block.difficulty(), 2);
+        assert!(block.hash.as_ref().unwrap().starts_with("00"));
+    }
+
+    #[test]
+    fn test_retarget_difficulty() {
+        assert_eq!(retarget_difficulty(5, TARGET_BLOCK_INTERVAL_MS), 5);
+        assert_eq!(retarget_difficulty(5, TARGET_BLOCK_INTERVAL_MS / 4), 6);
+        assert_eq!(retarget_difficulty(5, TARGET_BLOCK_INTERVAL_MS * 4), 4);
+    }
 }
\ No newline at end of file
@@ -1,14 +1,50 @@
+use crate::storage::Storage;
 use crate::traits::{Hashable, WorldState};
-use crate::types::{Account, AccountId, AccountType, Block, Chain, Error, Hash, Transaction};
-use std::collections::hash_map::Entry;
+use crate::types::{
+    retarget_difficulty, Account, AccountId, AccountState, AccountType, Block, Chain, Error, Hash,
+    StatusCache, Timestamp, Transaction,
+};
 use std::collections::HashMap;
-use std::fmt::format;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many recent block hashes transactions may anchor to for replay protection.
+const RECENT_BLOCKHASH_WINDOW: usize = 8;
+
+fn now_ms() -> Timestamp {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
 
-#[derive(Default, Debug)]
 pub struct Blockchain {
     blocks: Chain<Block>,
-    accounts: HashMap<AccountId, Account>,
+    accounts: AccountState,
     transaction_pool: Vec<Transaction>,
+    storage: Option<Storage>,
+    status_cache: StatusCache,
+}
+
+impl Default for Blockchain {
+    fn default() -> Self {
+        Blockchain {
+            blocks: Chain::default(),
+            accounts: AccountState::default(),
+            transaction_pool: Vec::new(),
+            storage: None,
+            status_cache: StatusCache::new(RECENT_BLOCKHASH_WINDOW),
+        }
+    }
+}
+
+impl std::fmt::Debug for Blockchain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blockchain")
+            .field("blocks", &self.blocks)
+            .field("accounts", &self.accounts)
+            .field("transaction_pool", &self.transaction_pool)
+            .finish()
+    }
 }
 
 impl Blockchain {
@@ -16,34 +52,190 @@ impl Blockchain {
         Default::default()
     }
 
+    /// Opens (creating if absent) a SQLite-backed chain at `path`, replaying any stored
+    /// blocks to rebuild in-memory state before returning.
+    pub fn open(path: &str) -> Result<Self, Error> {
+        let (storage, mut blockchain) = Storage::open(path)?;
+        blockchain.storage = Some(storage);
+        Ok(blockchain)
+    }
+
     pub fn len(&self) -> usize {
         self.blocks.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
     pub fn append_block(&mut self, block: Block) -> Result<(), Error> {
         if !block.verify() {
             return Err("Block has invalid hash".to_string());
         }
 
-        let is_genesis = self.blocks.len() == 0;
+        if !block.meets_difficulty() {
+            return Err("Block hash doesn't satisfy its stated difficulty".to_string());
+        }
+
+        let is_genesis = self.blocks.is_empty();
 
-        if block.transactions.len() == 0 {
+        if block.transactions.is_empty() {
             return Err("Block has not any transactions".to_string());
         }
 
-        let accounts_backup = self.accounts.clone();
+        self.accounts.checkpoint();
         for tx in &block.transactions {
+            if !is_genesis && !tx.verify_signature() {
+                self.accounts.revert_to_checkpoint();
+                return Err("Transaction signature is missing or invalid".to_string());
+            }
+
+            if !is_genesis {
+                match tx.recent_blockhash() {
+                    Some(anchor) if self.status_cache.is_recent_blockhash(anchor) => {
+                        if self.status_cache.has_processed(anchor, &tx.hash()) {
+                            self.accounts.revert_to_checkpoint();
+                            return Err("Transaction already processed".to_string());
+                        }
+                    }
+                    _ => {
+                        self.accounts.revert_to_checkpoint();
+                        return Err("Transaction has no recent blockhash anchor".to_string());
+                    }
+                }
+            }
+
+            self.accounts.checkpoint();
             let res = tx.execute(self, is_genesis);
-            if let Err(error) = res {
-                self.accounts = accounts_backup;
-                return Err(format!("Error during tx execution: {}", error));
+            match res {
+                Ok(()) => self.accounts.discard_checkpoint(),
+                Err(error) => {
+                    self.accounts.revert_to_checkpoint();
+                    self.accounts.revert_to_checkpoint();
+                    return Err(format!("Error during tx execution: {}", error));
+                }
+            }
+
+            if let Some(anchor) = tx.recent_blockhash() {
+                self.status_cache.record_transaction(anchor.clone(), tx.hash());
             }
         }
+        if let Some(storage) = &mut self.storage {
+            if let Err(error) = storage.append_block(self.blocks.len() + 1, &block) {
+                self.accounts.revert_to_checkpoint();
+                return Err(error);
+            }
+        }
+        self.accounts.discard_checkpoint();
 
+        self.status_cache.record_block_hash(block.hash());
         self.blocks.append(block);
         Ok(())
     }
 
+    /// Whether `hash` is within the recent-blockhash window transactions may anchor to.
+    pub fn is_recent_blockhash(&self, hash: &Hash) -> bool {
+        self.status_cache.is_recent_blockhash(hash)
+    }
+
+    /// Queues `tx` for inclusion in a future block, after checking its signature, that
+    /// it anchors to a recent block hash we haven't already processed it under, and
+    /// that its nonce is exactly the sender's expected next nonce.
+    pub fn submit_transaction(&mut self, tx: Transaction) -> Result<(), Error> {
+        if !tx.verify_signature() {
+            return Err("Transaction signature is missing or invalid".to_string());
+        }
+
+        match tx.recent_blockhash() {
+            Some(anchor) if self.status_cache.is_recent_blockhash(anchor) => {
+                if self.status_cache.has_processed(anchor, &tx.hash()) {
+                    return Err("Transaction already processed".to_string());
+                }
+            }
+            _ => return Err("Transaction has no recent blockhash anchor".to_string()),
+        }
+
+        let from = tx
+            .from()
+            .cloned()
+            .ok_or_else(|| "Transaction requires a from account".to_string())?;
+
+        let expected = self.expected_next_nonce(&from);
+        if tx.nonce() != expected {
+            return Err(format!(
+                "Transaction nonce {} does not match expected nonce {} for {}",
+                tx.nonce(),
+                expected,
+                from
+            ));
+        }
+
+        self.transaction_pool.push(tx);
+        Ok(())
+    }
+
+    /// Drains the mempool into a new block, ordered per-sender by ascending nonce. A
+    /// sender's transactions stop being included as soon as a gap is found in their
+    /// nonce sequence; anything after the gap is dropped from the pool rather than
+    /// retried in a later block, so a sender who submits out of order must resubmit
+    /// the skipped transactions themselves. The returned block has `prev_hash` set to
+    /// the current tip and still needs to be mined before it can be appended.
+    pub fn build_block(&mut self) -> Block {
+        let mut by_sender: HashMap<AccountId, Vec<Transaction>> = HashMap::new();
+        for tx in self.transaction_pool.drain(..) {
+            if let Some(from) = tx.from().cloned() {
+                by_sender.entry(from).or_default().push(tx);
+            }
+        }
+
+        let mut block = Block::new(self.get_last_block_hash());
+        block.set_timestamp(now_ms());
+        for (account_id, mut txs) in by_sender {
+            txs.sort_by_key(|tx| tx.nonce());
+            for (expected, tx) in (self.confirmed_nonce(&account_id)..).zip(txs) {
+                if tx.nonce() != expected {
+                    break;
+                }
+                block.add_transaction(tx);
+            }
+        }
+        block
+    }
+
+    /// The difficulty a freshly built block should be mined at: the chain tip's
+    /// difficulty retargeted by how long it actually took to produce, relative to
+    /// `TARGET_BLOCK_INTERVAL_MS`, measured as the timestamp delta between the tip and the
+    /// block before it. When the tip is the only block, the time elapsed since the tip's
+    /// timestamp is used instead. Defaults to `1` when there is no tip yet.
+    pub fn next_difficulty(&self) -> u32 {
+        match self.blocks.head() {
+            None => 1,
+            Some(tip) => {
+                let actual_interval_ms = match self.blocks.iter().nth(1) {
+                    Some(prev) => tip.timestamp().saturating_sub(prev.timestamp()),
+                    None => now_ms().saturating_sub(tip.timestamp()),
+                };
+                retarget_difficulty(tip.difficulty(), actual_interval_ms)
+            }
+        }
+    }
+
+    fn confirmed_nonce(&self, account_id: &AccountId) -> u128 {
+        self.accounts
+            .get_account_by_id(account_id)
+            .map(Account::nonce)
+            .unwrap_or(0)
+    }
+
+    fn expected_next_nonce(&self, account_id: &AccountId) -> u128 {
+        let pending = self
+            .transaction_pool
+            .iter()
+            .filter(|tx| tx.from() == Some(account_id))
+            .count() as u128;
+        self.confirmed_nonce(account_id) + pending
+    }
+
     pub fn validate(&self) -> Result<(), Error> {
         let mut block_num = self.blocks.len();
         let mut prev_block_hash: Option<Hash> = None;
@@ -55,6 +247,13 @@ impl Blockchain {
                 return Err(format!("Block {} has no hash", block_num));
             }
 
+            if !block.meets_difficulty() {
+                return Err(format!(
+                    "Block {} hash doesn't satisfy its stated difficulty",
+                    block_num
+                ));
+            }
+
             if !is_genesis && block.prev_hash.is_none() {
                 return Err(format!("Block {} doesn't have prev hash", block_num));
             }
@@ -95,21 +294,15 @@ impl WorldState for Blockchain {
         account_id: AccountId,
         account_type: AccountType,
     ) -> Result<(), Error> {
-        match self.accounts.entry(account_id.clone()) {
-            Entry::Occupied(_) => Err(format!("Account id already exist: {}", account_id)),
-            Entry::Vacant(v) => {
-                v.insert(Account::new(account_type));
-                Ok(())
-            }
-        }
+        self.accounts.create_account(account_id, account_type)
     }
 
     fn get_account_by_id(&self, account_id: AccountId) -> Option<&Account> {
-        self.accounts.get(&account_id)
+        self.accounts.get_account_by_id(&account_id)
     }
 
     fn get_account_by_id_mut(&mut self, account_id: AccountId) -> Option<&mut Account> {
-        self.accounts.get_mut(&account_id)
+        self.accounts.get_account_by_id_mut(&account_id)
     }
 }
 
@@ -118,6 +311,17 @@ mod tests {
     use super::*;
     use crate::types::TransactionData;
     use crate::utils::{append_block, append_block_with_tx, generate_account_id};
+    use ed25519_dalek::Keypair;
+    use rand::rngs::OsRng;
+
+    fn signed_create_account(anchor: Hash) -> Transaction {
+        let keypair = Keypair::generate(&mut OsRng);
+        let account_id = hex::encode(keypair.public.to_bytes());
+        let mut tx = Transaction::new(TransactionData::CreateAccount(account_id.clone()), Some(account_id));
+        tx.set_recent_blockhash(anchor);
+        tx.sign(&keypair);
+        tx
+    }
 
     #[test]
     fn test_new() {
@@ -137,10 +341,8 @@ mod tests {
         assert!(bc.append_block(block.clone()).is_ok());
 
         let mut block = Block::new(None);
-        let tx_create_account =
-            Transaction::new(TransactionData::CreateAccount(generate_account_id()), None);
         block.set_nonce(2);
-        block.add_transaction(tx_create_account);
+        block.add_transaction(signed_create_account(bc.get_last_block_hash().unwrap()));
 
         assert!(bc.append_block(block.clone()).is_ok());
 
@@ -208,20 +410,192 @@ mod tests {
         block.set_nonce(1);
         block.add_transaction(tx_create_account);
         block.add_transaction(tx_mint_initial_supply);
-        bc.append_block(block);
-        let mut block = Block::new(bc.get_last_block_hash());
-        let tx_create_alice =
-            Transaction::new(TransactionData::CreateAccount("alice".to_string()), None);
-        let tx_create_bob =
-            Transaction::new(TransactionData::CreateAccount("bob".to_string()), None);
+        let _ = bc.append_block(block);
+        let anchor = bc.get_last_block_hash().unwrap();
+        let mut block = Block::new(Some(anchor.clone()));
+        let tx_create_alice = signed_create_account(anchor.clone());
+        let alice_id = match &tx_create_alice.data {
+            TransactionData::CreateAccount(account_id) => account_id.clone(),
+            _ => unreachable!(),
+        };
+        let tx_create_bob = signed_create_account(anchor);
+        let bob_id = match &tx_create_bob.data {
+            TransactionData::CreateAccount(account_id) => account_id.clone(),
+            _ => unreachable!(),
+        };
         block.set_nonce(2);
         block.add_transaction(tx_create_alice);
         block.add_transaction(tx_create_bob.clone());
         block.add_transaction(tx_create_bob);
         assert!(bc.append_block(block).is_err());
         assert!(bc.get_account_by_id("satoshi".to_string()).is_some());
-        assert!(bc.get_account_by_id("alice".to_string()).is_none());
-        assert!(bc.get_account_by_id("bob".to_string()).is_none());
+        assert!(bc.get_account_by_id(alice_id).is_none());
+        assert!(bc.get_account_by_id(bob_id).is_none());
+    }
+
+    #[test]
+    fn test_transfer_moves_balance() {
+        let mut bc = Blockchain::new();
+        let sender_keypair = Keypair::generate(&mut OsRng);
+        let sender_id = hex::encode(sender_keypair.public.to_bytes());
+        let receiver_keypair = Keypair::generate(&mut OsRng);
+        let receiver_id = hex::encode(receiver_keypair.public.to_bytes());
+
+        let tx_create_sender =
+            Transaction::new(TransactionData::CreateAccount(sender_id.clone()), None);
+        let tx_create_receiver =
+            Transaction::new(TransactionData::CreateAccount(receiver_id.clone()), None);
+        let tx_mint = Transaction::new(
+            TransactionData::MintInitialSupply {
+                to: sender_id.clone(),
+                amount: 100,
+            },
+            None,
+        );
+        let mut block = Block::new(None);
+        block.set_nonce(1);
+        block.add_transaction(tx_create_sender);
+        block.add_transaction(tx_create_receiver);
+        block.add_transaction(tx_mint);
+        assert!(bc.append_block(block).is_ok());
+
+        let mut tx_transfer = Transaction::new(
+            TransactionData::Transfer {
+                to: receiver_id.clone(),
+                amount: 40,
+            },
+            Some(sender_id.clone()),
+        );
+        tx_transfer.set_recent_blockhash(bc.get_last_block_hash().unwrap());
+        tx_transfer.sign(&sender_keypair);
+
+        let mut block = Block::new(bc.get_last_block_hash());
+        block.set_nonce(2);
+        block.add_transaction(tx_transfer);
+        assert!(bc.append_block(block).is_ok());
+
+        assert_eq!(bc.get_account_by_id(sender_id).unwrap().balance, 60);
+        assert_eq!(bc.get_account_by_id(receiver_id).unwrap().balance, 40);
+    }
+
+    #[test]
+    fn test_transfer_rejects_unsigned() {
+        let mut bc = Blockchain::new();
+        let sender_keypair = Keypair::generate(&mut OsRng);
+        let sender_id = hex::encode(sender_keypair.public.to_bytes());
+        let receiver_id = generate_account_id();
+
+        let tx_create_sender =
+            Transaction::new(TransactionData::CreateAccount(sender_id.clone()), None);
+        let tx_create_receiver =
+            Transaction::new(TransactionData::CreateAccount(receiver_id.clone()), None);
+        let tx_mint = Transaction::new(
+            TransactionData::MintInitialSupply {
+                to: sender_id.clone(),
+                amount: 100,
+            },
+            None,
+        );
+        let mut block = Block::new(None);
+        block.set_nonce(1);
+        block.add_transaction(tx_create_sender);
+        block.add_transaction(tx_create_receiver);
+        block.add_transaction(tx_mint);
+        assert!(bc.append_block(block).is_ok());
+
+        let tx_transfer = Transaction::new(
+            TransactionData::Transfer {
+                to: receiver_id,
+                amount: 40,
+            },
+            Some(sender_id),
+        );
+
+        let mut block = Block::new(bc.get_last_block_hash());
+        block.set_nonce(2);
+        block.add_transaction(tx_transfer);
+        assert!(bc.append_block(block).is_err());
+    }
+
+    fn setup_funded_sender(bc: &mut Blockchain) -> (Keypair, AccountId) {
+        let sender_keypair = Keypair::generate(&mut OsRng);
+        let sender_id = hex::encode(sender_keypair.public.to_bytes());
+
+        let tx_create_sender =
+            Transaction::new(TransactionData::CreateAccount(sender_id.clone()), None);
+        let tx_mint = Transaction::new(
+            TransactionData::MintInitialSupply {
+                to: sender_id.clone(),
+                amount: 100,
+            },
+            None,
+        );
+        let mut block = Block::new(None);
+        block.set_nonce(1);
+        block.add_transaction(tx_create_sender);
+        block.add_transaction(tx_mint);
+        bc.append_block(block).unwrap();
+
+        (sender_keypair, sender_id)
+    }
+
+    fn signed_transfer(
+        keypair: &Keypair,
+        from: &AccountId,
+        to: AccountId,
+        amount: u128,
+        nonce: u128,
+        anchor: &Hash,
+    ) -> Transaction {
+        let mut tx = Transaction::new(TransactionData::Transfer { to, amount }, Some(from.clone()));
+        tx.set_nonce(nonce);
+        tx.set_recent_blockhash(anchor.clone());
+        tx.sign(keypair);
+        tx
+    }
+
+    #[test]
+    fn test_submit_transaction_rejects_wrong_nonce() {
+        let mut bc = Blockchain::new();
+        let (sender_keypair, sender_id) = setup_funded_sender(&mut bc);
+        let receiver_id = generate_account_id();
+        let anchor = bc.get_last_block_hash().unwrap();
+
+        let tx = signed_transfer(&sender_keypair, &sender_id, receiver_id, 10, 1, &anchor);
+        assert!(bc.submit_transaction(tx).is_err());
+    }
+
+    #[test]
+    fn test_build_block_orders_by_nonce_and_skips_gaps() {
+        let mut bc = Blockchain::new();
+        let (sender_keypair, sender_id) = setup_funded_sender(&mut bc);
+
+        let tx_create_receiver = signed_create_account(bc.get_last_block_hash().unwrap());
+        let receiver_id = match &tx_create_receiver.data {
+            TransactionData::CreateAccount(account_id) => account_id.clone(),
+            _ => unreachable!(),
+        };
+        let mut receiver_block = Block::new(bc.get_last_block_hash());
+        receiver_block.add_transaction(tx_create_receiver);
+        receiver_block.mine(bc.next_difficulty());
+        bc.append_block(receiver_block).unwrap();
+
+        let anchor = bc.get_last_block_hash().unwrap();
+        let tx1 = signed_transfer(&sender_keypair, &sender_id, receiver_id.clone(), 10, 0, &anchor);
+        let tx2 = signed_transfer(&sender_keypair, &sender_id, receiver_id.clone(), 10, 1, &anchor);
+        let tx_gap = signed_transfer(&sender_keypair, &sender_id, receiver_id, 10, 3, &anchor);
+
+        assert!(bc.submit_transaction(tx2.clone()).is_err());
+        assert!(bc.submit_transaction(tx1).is_ok());
+        assert!(bc.submit_transaction(tx2).is_ok());
+        bc.transaction_pool.push(tx_gap);
+
+        let mut block = bc.build_block();
+        assert_eq!(block.transactions.len(), 2);
+        block.mine(bc.next_difficulty());
+        assert!(bc.append_block(block).is_ok());
+
+        assert_eq!(bc.get_account_by_id(sender_id).unwrap().balance, 80);
     }
 
     #[test]
@@ -245,14 +619,16 @@ mod tests {
 
         assert!(bc.validate().is_ok());
 
-        let mut it = bc.blocks.iter_mut();
-        it.next();
-        it.next();
-        let block = it.next().unwrap();
-        block.transactions[1].data = TransactionData::MintInitialSupply {
-            to: "satoshi".to_string(),
-            amount: 100,
-        };
+        {
+            let mut it = bc.blocks.iter_mut();
+            it.next();
+            it.next();
+            let block = it.next().unwrap();
+            block.transactions[1].data = TransactionData::MintInitialSupply {
+                to: "satoshi".to_string(),
+                amount: 100,
+            };
+        }
 
         assert!(bc.validate().is_err());
     }
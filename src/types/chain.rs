@@ -0,0 +1,69 @@
+/// An append-only sequence of items, newest-last in storage but iterated newest-first
+/// (matching how `Blockchain::validate` walks back from the tip to the genesis block).
+#[derive(Debug, Clone)]
+pub struct Chain<T> {
+    items: Vec<T>,
+}
+
+impl<T> Default for Chain<T> {
+    fn default() -> Self {
+        Chain { items: Vec::new() }
+    }
+}
+
+impl<T> Chain<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn append(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    /// The most recently appended item, if any.
+    pub fn head(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    /// Iterates from the most recently appended item back to the first.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter().rev()
+    }
+
+    /// Iterates mutably from the most recently appended item back to the first.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.items.iter_mut().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_head() {
+        let mut chain = Chain::new();
+        assert_eq!(chain.head(), None);
+        chain.append(1);
+        chain.append(2);
+        assert_eq!(chain.head(), Some(&2));
+        assert_eq!(chain.len(), 2);
+    }
+
+    #[test]
+    fn test_iter_is_newest_first() {
+        let mut chain = Chain::new();
+        chain.append(1);
+        chain.append(2);
+        chain.append(3);
+        assert_eq!(chain.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+    }
+}
@@ -1,14 +1,18 @@
 mod transaction;
 mod account;
+mod account_state;
 mod blockchain;
 mod block;
 mod chain;
+mod status_cache;
 
 pub use chain::Chain;
 pub use account::{Account, AccountType};
+pub use account_state::AccountState;
 pub use transaction::{Transaction, TransactionData};
 pub use blockchain::Blockchain;
-pub use block::Block;
+pub use block::{retarget_difficulty, Block};
+pub use status_cache::StatusCache;
 
 pub type Hash = String;
 pub type Timestamp = u128;
@@ -0,0 +1,91 @@
+use crate::types::Hash;
+use std::collections::{HashSet, VecDeque};
+
+/// Tracks recently seen block hashes and which transaction hashes have already been
+/// processed against each one, modeled on Solana's status cache / recent-blockhashes
+/// design: a transaction references a recent block hash as its "anchor", and is
+/// rejected as a duplicate if its hash was already processed under that anchor.
+///
+/// Anchors older than `window` are evicted in FIFO order, along with everything
+/// recorded under them, so lookups stay O(1) instead of growing forever.
+#[derive(Debug)]
+pub struct StatusCache {
+    window: usize,
+    anchors: VecDeque<Hash>,
+    processed: std::collections::HashMap<Hash, HashSet<Hash>>,
+}
+
+impl StatusCache {
+    pub fn new(window: usize) -> Self {
+        StatusCache {
+            window,
+            anchors: VecDeque::new(),
+            processed: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Whether `hash` is one of the last `window` block hashes recorded.
+    pub fn is_recent_blockhash(&self, hash: &Hash) -> bool {
+        self.anchors.contains(hash)
+    }
+
+    /// Whether `tx_hash` has already been recorded against `anchor`.
+    pub fn has_processed(&self, anchor: &Hash, tx_hash: &Hash) -> bool {
+        self.processed
+            .get(anchor)
+            .is_some_and(|seen| seen.contains(tx_hash))
+    }
+
+    /// Marks `tx_hash` as processed under `anchor`, so a later duplicate referencing
+    /// the same anchor is rejected by `has_processed`.
+    pub fn record_transaction(&mut self, anchor: Hash, tx_hash: Hash) {
+        self.processed.entry(anchor).or_default().insert(tx_hash);
+    }
+
+    /// Records a newly mined block hash as the most recent anchor, evicting the
+    /// oldest one (and everything processed under it) once `window` is exceeded.
+    pub fn record_block_hash(&mut self, hash: Hash) {
+        self.anchors.push_back(hash);
+        if self.anchors.len() > self.window {
+            if let Some(expired) = self.anchors.pop_front() {
+                self.processed.remove(&expired);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recognizes_recent_blockhash() {
+        let mut cache = StatusCache::new(2);
+        cache.record_block_hash("a".to_string());
+        assert!(cache.is_recent_blockhash(&"a".to_string()));
+        assert!(!cache.is_recent_blockhash(&"b".to_string()));
+    }
+
+    #[test]
+    fn test_rejects_duplicate_under_same_anchor() {
+        let mut cache = StatusCache::new(2);
+        cache.record_block_hash("a".to_string());
+        cache.record_transaction("a".to_string(), "tx1".to_string());
+        assert!(cache.has_processed(&"a".to_string(), &"tx1".to_string()));
+        assert!(!cache.has_processed(&"a".to_string(), &"tx2".to_string()));
+    }
+
+    #[test]
+    fn test_expires_old_anchors_and_their_entries() {
+        let mut cache = StatusCache::new(2);
+        cache.record_block_hash("a".to_string());
+        cache.record_transaction("a".to_string(), "tx1".to_string());
+        cache.record_block_hash("b".to_string());
+        cache.record_block_hash("c".to_string());
+
+        assert!(!cache.is_recent_blockhash(&"a".to_string()));
+        assert!(!cache.has_processed(&"a".to_string(), &"tx1".to_string()));
+        assert!(cache.is_recent_blockhash(&"b".to_string()));
+        assert!(cache.is_recent_blockhash(&"c".to_string()));
+    }
+}
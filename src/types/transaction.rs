@@ -2,14 +2,19 @@ use crate::traits::{Hashable, WorldState};
 use crate::types::{AccountId, AccountType, Balance, Error, Hash, Timestamp};
 use blake2::digest::FixedOutput;
 use blake2::{Blake2s, Digest};
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
     nonce: u128,
     timestamp: Timestamp,
     from: Option<AccountId>,
     pub(crate) data: TransactionData,
     signature: Option<String>,
+    /// Hash of a recent block this transaction was built against, used as its
+    /// replay-protection anchor (see `StatusCache`).
+    recent_blockhash: Option<Hash>,
 }
 
 impl Transaction {
@@ -20,9 +25,71 @@ impl Transaction {
             data,
             from,
             signature: None,
+            recent_blockhash: None,
         }
     }
 
+    pub fn nonce(&self) -> u128 {
+        self.nonce
+    }
+
+    pub fn from(&self) -> Option<&AccountId> {
+        self.from.as_ref()
+    }
+
+    pub fn recent_blockhash(&self) -> Option<&Hash> {
+        self.recent_blockhash.as_ref()
+    }
+
+    /// Sets the transaction's nonce. Must be called before `sign`, since the nonce is
+    /// part of the signed payload.
+    pub fn set_nonce(&mut self, nonce: u128) {
+        self.nonce = nonce;
+    }
+
+    /// Anchors the transaction to `blockhash`, a recent tip the sender observed. Must be
+    /// called before `sign`, since it is part of the signed payload.
+    pub fn set_recent_blockhash(&mut self, blockhash: Hash) {
+        self.recent_blockhash = Some(blockhash);
+    }
+
+    /// Signs the transaction with `keypair`, authenticating `(nonce, timestamp, from, data,
+    /// recent_blockhash)`.
+    ///
+    /// The signed message is the same Blake2s digest used for `hash()`, so the signature
+    /// covers exactly the fields that are hashed.
+    pub fn sign(&mut self, keypair: &Keypair) {
+        let message = self.signing_message();
+        self.signature = Some(hex::encode(keypair.sign(&message).to_bytes()));
+    }
+
+    /// Verifies `signature` against the ed25519 public key derived from `from`.
+    ///
+    /// Returns `false` if there is no `from`/`signature` pair, if either fails to decode, or
+    /// if the signature doesn't match the transaction's payload.
+    pub fn verify_signature(&self) -> bool {
+        let (from, signature) = match (&self.from, &self.signature) {
+            (Some(from), Some(signature)) => (from, signature),
+            _ => return false,
+        };
+
+        let public_key = match hex::decode(from).ok().and_then(|bytes| PublicKey::from_bytes(&bytes).ok()) {
+            Some(public_key) => public_key,
+            None => return false,
+        };
+
+        let signature = match hex::decode(signature).ok().and_then(|bytes| Signature::from_bytes(&bytes).ok()) {
+            Some(signature) => signature,
+            None => return false,
+        };
+
+        public_key.verify(&self.signing_message(), &signature).is_ok()
+    }
+
+    fn signing_message(&self) -> Vec<u8> {
+        hex::decode(self.hash()).expect("hash() always returns valid hex")
+    }
+
     pub fn execute<T: WorldState>(&self, state: &mut T, is_genesis: bool) -> Result<(), Error> {
         match &self.data {
             TransactionData::CreateAccount(account_id) => {
@@ -40,7 +107,33 @@ impl Transaction {
                     }
                 }
             }
-            _ => Err("Unknown transaction".to_string()),
+            TransactionData::Transfer { to, amount } => {
+                let from = self
+                    .from
+                    .clone()
+                    .ok_or_else(|| "Transfer requires a from account".to_string())?;
+
+                match state.get_account_by_id(from.clone()) {
+                    None => return Err("Invalid account".to_string()),
+                    Some(account) if account.balance < *amount => {
+                        return Err("Insufficient balance".to_string())
+                    }
+                    Some(account) if account.nonce != self.nonce => {
+                        return Err("Invalid nonce".to_string())
+                    }
+                    Some(_) => {}
+                }
+
+                if state.get_account_by_id(to.clone()).is_none() {
+                    return Err("Invalid account".to_string());
+                }
+
+                let sender = state.get_account_by_id_mut(from).unwrap();
+                sender.balance -= amount;
+                sender.nonce += 1;
+                state.get_account_by_id_mut(to.clone()).unwrap().balance += amount;
+                Ok(())
+            }
         }
     }
 }
@@ -54,14 +147,15 @@ impl Hashable for Transaction {
                 self.nonce,
                 self.timestamp,
                 self.from.clone(),
-                self.data.clone()
+                self.data.clone(),
+                self.recent_blockhash.clone()
             )
         ));
         hex::encode(hasher.finalize_fixed())
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TransactionData {
     CreateAccount(AccountId),
     MintInitialSupply { to: AccountId, amount: Balance },
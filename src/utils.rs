@@ -0,0 +1,46 @@
+//! Test-only helpers for building and appending blocks without repeating the same
+//! signing/anchoring boilerplate in every test.
+use crate::types::{Block, Blockchain, Error, Transaction, TransactionData};
+use ed25519_dalek::Keypair;
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+/// A random, non-signing account id for tests that just need a unique destination
+/// account rather than a real keypair.
+pub fn generate_account_id() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Builds and appends a block containing `txs`, mined at the chain's current
+/// difficulty and anchored to its current tip.
+pub fn append_block_with_tx(
+    bc: &mut Blockchain,
+    nonce: u128,
+    txs: Vec<Transaction>,
+) -> Result<(), Error> {
+    let mut block = Block::new(bc.get_last_block_hash());
+    block.set_nonce(nonce);
+    for tx in txs {
+        block.add_transaction(tx);
+    }
+    block.mine(bc.next_difficulty());
+    bc.append_block(block)
+}
+
+/// Appends a block creating a single fresh, signed account, anchored to the chain's
+/// current tip so non-genesis replay-protection checks are satisfied. Panics if the
+/// block is rejected, since callers use this purely to advance the chain.
+pub fn append_block(bc: &mut Blockchain, nonce: u128) {
+    let keypair = Keypair::generate(&mut OsRng);
+    let account_id = hex::encode(keypair.public.to_bytes());
+    let mut tx = Transaction::new(
+        TransactionData::CreateAccount(account_id.clone()),
+        Some(account_id),
+    );
+    tx.set_recent_blockhash(bc.get_last_block_hash().unwrap());
+    tx.sign(&keypair);
+
+    append_block_with_tx(bc, nonce, vec![tx]).unwrap();
+}